@@ -1,3 +1,5 @@
+use crate::quorum::QuorumProvider;
+use crate::retry::{is_rate_limit_error, with_retry, RetryConfig};
 use crate::sql::DbClient;
 use alloy::contract::{ContractInstance, Interface};
 use alloy::dyn_abi::DynSolValue;
@@ -7,12 +9,73 @@ use alloy::providers::Provider;
 use alloy::rpc::types::{BlockNumberOrTag::Latest, Filter, Log};
 use alloy::sol;
 use eyre::Result;
+use futures_util::future::BoxFuture;
+use futures_util::StreamExt;
 use reqwest::Client;
 use serde::Deserialize;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 use tiny_keccak::Keccak;
 
+const DEFAULT_BACKFILL_CHUNK_BLOCKS: u64 = 49999;
+const DEFAULT_ENS_CACHE_TTL_SECS: u64 = 300;
+const ENS_REGISTRY_ADDRESS: &str = "0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e";
+/// How many blocks of disagreement to tolerate between donation RPC
+/// backends on "latest block", configurable via `LATEST_BLOCK_TOLERANCE`.
+const DEFAULT_LATEST_BLOCK_TOLERANCE: u64 = 5;
+
+/// Caches resolved `address -> verified ENS name` results for a TTL
+/// (configurable via `ENS_CACHE_TTL_SECS`) so repeated polling cycles don't
+/// re-run the reverse + forward resolution round trip for the same address.
+pub struct EnsCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<Address, (Option<String>, Instant)>>,
+}
+
+impl EnsCache {
+    pub fn from_env() -> Self {
+        let ttl_secs = std::env::var("ENS_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(DEFAULT_ENS_CACHE_TTL_SECS);
+        Self {
+            ttl: Duration::from_secs(ttl_secs),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn get(&self, address: &Address) -> Option<Option<String>> {
+        let entries = self.entries.lock().unwrap();
+        entries.get(address).and_then(|(name, cached_at)| {
+            (cached_at.elapsed() < self.ttl).then(|| name.clone())
+        })
+    }
+
+    fn insert(&self, address: Address, name: Option<String>) {
+        let mut entries = self.entries.lock().unwrap();
+        // Sweep expired entries on every insert so a long-running process
+        // doesn't accumulate one entry per unique address forever; `get`
+        // alone only ever hides expired entries, never reclaims them.
+        let ttl = self.ttl;
+        entries.retain(|_, (_, cached_at)| cached_at.elapsed() < ttl);
+        entries.insert(address, (name, Instant::now()));
+    }
+}
+
+/// Returns true if `message` indicates the RPC rejected a `get_logs` range
+/// because it would return too many results, as opposed to a rate limit or
+/// other transport error.
+fn is_range_too_large_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("query returned more than")
+        || lower.contains("response size exceeded")
+        || (lower.contains("limit exceeded") && !lower.contains("rate limit"))
+}
+
 #[derive(Debug, Deserialize)]
 struct TransactionInfo {
     from: String,
@@ -24,18 +87,33 @@ struct TransactionInfo {
 #[derive(Debug, Deserialize)]
 struct EtherscanResponse {
     status: String,
+    #[serde(default)]
+    message: String,
     result: Vec<TransactionInfo>,
 }
 
-pub struct Checker<P: Provider> {
+pub struct Checker<PT: Provider + Clone, PD: Provider + Clone> {
     target_address: String,
     etherscan_api_key: String,
     http_client: Client,
     pg_client: DbClient,
-    provider: P,
+    provider_transfer: QuorumProvider<PT>,
+    provider_donation: QuorumProvider<PD>,
     start_block: u64,
     chain_id: u64,
     filter: Filter,
+    retry_config: RetryConfig,
+    /// Starting (and max) window size for the adaptive donation log backfill,
+    /// configurable via `BACKFILL_CHUNK_BLOCKS`.
+    max_backfill_chunk: u64,
+    /// Whether `provider_donation` is a `ws://`/`wss://` pubsub transport, in
+    /// which case `run` streams donations via `eth_subscribe` instead of
+    /// polling `get_logs` every 20s.
+    use_donation_subscription: bool,
+    ens_cache: EnsCache,
+    /// Max disagreement (in blocks) to tolerate between donation backends
+    /// when picking a "latest block" to trust, via `LATEST_BLOCK_TOLERANCE`.
+    latest_block_tolerance: u64,
 }
 
 sol! {
@@ -44,12 +122,15 @@ sol! {
     }
 }
 
-impl<P: Provider> Checker<P> {
+impl<PT: Provider + Clone, PD: Provider + Clone> Checker<PT, PD> {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         target_transfer_address: String,
         target_donation_address: String,
         etherscan_api_key: String,
-        provider: P,
+        provider_transfer: QuorumProvider<PT>,
+        provider_donation: QuorumProvider<PD>,
+        use_donation_subscription: bool,
         http_client: Client,
         pg_client: DbClient,
         start_block: u64,
@@ -58,7 +139,8 @@ impl<P: Provider> Checker<P> {
         Self {
             target_address: target_transfer_address.clone(),
             etherscan_api_key,
-            provider,
+            provider_transfer,
+            provider_donation,
             http_client,
             pg_client,
             start_block,
@@ -67,6 +149,17 @@ impl<P: Provider> Checker<P> {
                     Address::from_str(&target_donation_address.clone()).unwrap()
                 ]),
             chain_id,
+            retry_config: RetryConfig::from_env(),
+            max_backfill_chunk: std::env::var("BACKFILL_CHUNK_BLOCKS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_BACKFILL_CHUNK_BLOCKS),
+            use_donation_subscription,
+            ens_cache: EnsCache::from_env(),
+            latest_block_tolerance: std::env::var("LATEST_BLOCK_TOLERANCE")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_LATEST_BLOCK_TOLERANCE),
         }
     }
 
@@ -75,48 +168,223 @@ impl<P: Provider> Checker<P> {
             eprintln!("Error processing past logs: {}", err);
         }
 
-        loop {
-            if let Err(e) = self.check_transfers().await {
-                println!("Error checking transfers: {}", e);
+        // The donation subscription is a long-lived stream that can block
+        // for hours/days while healthy, so it runs in its own loop rather
+        // than sequenced inline with transfer polling below — otherwise a
+        // healthy subscription would starve `check_transfers` for as long
+        // as it stays connected.
+        let donation_loop = async {
+            if !self.use_donation_subscription {
+                std::future::pending::<()>().await;
             }
-            if let Err(e) = self.process_new_logs().await {
-                println!("Error checking donations: {}", e);
+            loop {
+                if let Err(e) = self.run_donation_subscription().await {
+                    println!(
+                        "Donation subscription dropped ({}), falling back to polling",
+                        e
+                    );
+                }
+                if let Err(e) = self.process_new_logs().await {
+                    println!("Error checking donations: {}", e);
+                }
+            }
+        };
+
+        let transfer_loop = async {
+            loop {
+                if let Err(e) = self.check_transfers().await {
+                    println!("Error checking transfers: {}", e);
+                }
+                if !self.use_donation_subscription {
+                    if let Err(e) = self.process_new_logs().await {
+                        println!("Error checking donations: {}", e);
+                    }
+                }
+                tokio::time::sleep(tokio::time::Duration::from_secs(20)).await;
+            }
+        };
+
+        tokio::join!(donation_loop, transfer_loop);
+    }
+
+    /// Opens an `eth_subscribe` pubsub subscription on the donation filter
+    /// and streams logs into `process_donation_event` as they arrive,
+    /// instead of re-scanning the last 64 blocks every 20 seconds. Runs
+    /// until the subscription stream ends (e.g. the socket drops), at which
+    /// point it backfills any blocks it missed before returning control to
+    /// the polling loop in `run`.
+    async fn run_donation_subscription(&self) -> Result<()> {
+        let mut last_seen_block = self.latest_block_number().await?;
+        // Subscriptions are a long-lived stream, not a single request/response,
+        // so they aren't quorum-checked like `get_block_by_number`/`get_logs`;
+        // we subscribe to the first configured donation backend only.
+        let subscription = self
+            .provider_donation
+            .primary()
+            .subscribe_logs(&self.filter)
+            .await?;
+        let mut stream = subscription.into_stream();
+        println!("📡 Subscribed to donation logs via eth_subscribe");
+
+        while let Some(log) = stream.next().await {
+            if let Some(block_number) = log.block_number {
+                last_seen_block = last_seen_block.max(block_number);
             }
-            tokio::time::sleep(tokio::time::Duration::from_secs(20)).await;
+            self.process_donation_event(log).await?;
         }
+
+        self.backfill_logs_from(last_seen_block).await
     }
 
-    pub async fn process_past_logs(&self) -> Result<()> {
-        let block = self.provider.get_block_by_number(Latest).await?;
-        let mut current_end_block = block.unwrap().header.number;
-
-        while self.start_block < current_end_block {
-            let current_start_block = if current_end_block >= 49999 {
-                current_end_block - 49999
-            } else {
-                0
-            };
+    /// Returns the highest block number that a quorum of donation backends
+    /// report as reached, or as at most `latest_block_tolerance` blocks
+    /// ahead of their own head. "Latest block" is
+    /// inherently a moving target, so independent backends will rarely
+    /// return byte-identical numbers even when all are healthy — but unlike
+    /// that plain equality check, still requires quorum agreement, so a
+    /// single malicious or buggy backend can't unilaterally advance the
+    /// tracked chain head to unmined blocks, which would otherwise poison
+    /// `fetch_logs_adaptive`'s range scan and prematurely checkpoint past
+    /// blocks that haven't happened yet.
+    async fn latest_block_number(&self) -> Result<u64> {
+        with_retry(&self.retry_config, || async {
+            self.provider_donation
+                .call_latest_block(self.latest_block_tolerance, |p| async move {
+                    let block = p
+                        .get_block_by_number(Latest)
+                        .await
+                        .map_err(|e| eyre::eyre!(e))?;
+                    block
+                        .map(|b| b.header.number)
+                        .ok_or_else(|| eyre::eyre!("no latest block returned"))
+                })
+                .await
+        })
+        .await
+    }
 
-            let filter = self
-                .filter
-                .clone()
-                .from_block(current_start_block)
-                .to_block(current_end_block);
+    /// Backfills from the block after the last checkpointed block (or from
+    /// `start_block` if there isn't one yet) so a crash mid-backfill resumes instead of
+    /// re-scanning from the beginning.
+    pub async fn process_past_logs(&self) -> Result<()> {
+        let resume_from = self
+            .pg_client
+            .get_backfill_checkpoint()
+            .await?
+            .map(|block| block + 1)
+            .unwrap_or(self.start_block)
+            .max(self.start_block);
+        self.backfill_logs_from(resume_from).await
+    }
 
-            // Fetch logs
-            let logs = self.provider.get_logs(&filter).await?;
+    /// Scans donation logs from `from_block` up to the current head, used
+    /// both for the initial backfill and to close the gap left when a
+    /// donation subscription drops. The window size starts at
+    /// `max_backfill_chunk` and adapts: a range that a backend rejects as
+    /// too large is halved and retried, and the window grows back toward
+    /// `max_backfill_chunk` after each successful fetch. Each window's end
+    /// block is persisted as the backfill checkpoint as soon as it's fully
+    /// processed.
+    async fn backfill_logs_from(&self, from_block: u64) -> Result<()> {
+        let current_head = self.latest_block_number().await?;
+        let chunk_size = AtomicU64::new(self.max_backfill_chunk);
+        let mut window_start = from_block;
+
+        while window_start <= current_head {
+            let window_end = window_start
+                .saturating_add(chunk_size.load(Ordering::SeqCst).saturating_sub(1))
+                .min(current_head);
+
+            let logs = self
+                .fetch_logs_adaptive(window_start, window_end, &chunk_size)
+                .await?;
             for log in logs {
                 self.process_donation_event(log.clone()).await?;
             }
 
-            current_end_block = current_start_block;
+            self.pg_client.set_backfill_checkpoint(window_end).await?;
+            window_start = window_end + 1;
         }
         Ok(())
     }
 
+    /// Fetches donation logs for `[from_block, to_block]` via quorum. If a
+    /// backend reports the range is too large, halves it and retries each
+    /// half recursively; on success, grows `chunk_size` back toward
+    /// `max_backfill_chunk` for the next window `backfill_logs_from` asks for.
+    fn fetch_logs_adaptive<'a>(
+        &'a self,
+        from_block: u64,
+        to_block: u64,
+        chunk_size: &'a AtomicU64,
+    ) -> BoxFuture<'a, Result<Vec<Log>>> {
+        Box::pin(async move {
+            let filter = self.filter.clone().from_block(from_block).to_block(to_block);
+            let fetch_once = || {
+                let filter = filter.clone();
+                async move {
+                    self.provider_donation
+                        .call(|p| {
+                            let filter = filter.clone();
+                            async move {
+                                let mut logs =
+                                    p.get_logs(&filter).await.map_err(|e| eyre::eyre!(e))?;
+                                // Different backends may return the same logs in a
+                                // different order; sort before comparing so quorum
+                                // agreement isn't defeated by reordering alone.
+                                logs.sort_by_key(|l| {
+                                    (l.block_number.unwrap_or_default(), l.log_index.unwrap_or_default())
+                                });
+                                Ok(logs)
+                            }
+                        })
+                        .await
+                }
+            };
+
+            // A "range too large" response is deterministic for a given
+            // window, so check for it on the very first attempt before
+            // `with_retry` burns its backoff budget retrying something that
+            // can never succeed; only genuine transport/rate-limit errors
+            // go through the retry wrapper.
+            let result = match fetch_once().await {
+                Err(e) if to_block > from_block && is_range_too_large_error(&e.to_string()) => {
+                    Err(e)
+                }
+                Err(_) => with_retry(&self.retry_config, fetch_once).await,
+                ok => ok,
+            };
+
+            match result {
+                Ok(logs) => {
+                    let grown = chunk_size
+                        .load(Ordering::SeqCst)
+                        .saturating_mul(2)
+                        .min(self.max_backfill_chunk);
+                    chunk_size.store(grown, Ordering::SeqCst);
+                    Ok(logs)
+                }
+                Err(e) if to_block > from_block && is_range_too_large_error(&e.to_string()) => {
+                    let mid = from_block + (to_block - from_block) / 2;
+                    chunk_size.store((mid - from_block + 1).max(1), Ordering::SeqCst);
+                    println!(
+                        "📉 donation log range {}..{} too large, splitting at block {}",
+                        from_block, to_block, mid
+                    );
+                    let mut head = self.fetch_logs_adaptive(from_block, mid, chunk_size).await?;
+                    let mut tail = self
+                        .fetch_logs_adaptive(mid + 1, to_block, chunk_size)
+                        .await?;
+                    head.append(&mut tail);
+                    Ok(head)
+                }
+                Err(e) => Err(e),
+            }
+        })
+    }
+
     pub async fn process_new_logs(&self) -> Result<()> {
-        let block = self.provider.get_block_by_number(Latest).await?;
-        let current_end_block = block.unwrap().header.number;
+        let current_end_block = self.latest_block_number().await?;
         let current_start_block = if current_end_block >= 64 {
             current_end_block - 64
         } else {
@@ -127,7 +395,21 @@ impl<P: Provider> Checker<P> {
             .clone()
             .from_block(current_start_block)
             .to_block(current_end_block);
-        let logs = self.provider.get_logs(&filter).await?;
+        let logs = with_retry(&self.retry_config, || async {
+            self.provider_donation
+                .call(|p| {
+                    let filter = filter.clone();
+                    async move {
+                        let mut logs = p.get_logs(&filter).await.map_err(|e| eyre::eyre!(e))?;
+                        logs.sort_by_key(|l| {
+                            (l.block_number.unwrap_or_default(), l.log_index.unwrap_or_default())
+                        });
+                        Ok(logs)
+                    }
+                })
+                .await
+        })
+        .await?;
         for log in logs {
             self.process_donation_event(log.clone()).await?;
         }
@@ -142,13 +424,10 @@ impl<P: Provider> Checker<P> {
             self.etherscan_api_key
         );
 
-        let normal_response = self
-            .http_client
-            .get(&normal_url)
-            .send()
-            .await?
-            .json::<EtherscanResponse>()
-            .await?;
+        let normal_response = with_retry(&self.retry_config, || async {
+            self.fetch_etherscan(&normal_url).await
+        })
+        .await?;
 
         // Fetch internal transactions
         let internal_url = format!(
@@ -158,13 +437,10 @@ impl<P: Provider> Checker<P> {
             self.etherscan_api_key
         );
 
-        let internal_response = self
-            .http_client
-            .get(&internal_url)
-            .send()
-            .await?
-            .json::<EtherscanResponse>()
-            .await?;
+        let internal_response = with_retry(&self.retry_config, || async {
+            self.fetch_etherscan(&internal_url).await
+        })
+        .await?;
 
         if normal_response.status != "1" && internal_response.status != "1" {
             println!("Error fetching data: both responses failed.");
@@ -196,9 +472,20 @@ impl<P: Provider> Checker<P> {
 
                     // If it's a new transaction: resolve ENS
                     let from_address = Address::from_str(&tx.from)?;
-                    let from_display = match resolve_ens_name(&self.provider, from_address).await {
-                        Some(name) => name,
-                        None => format!("{:?}", from_address),
+                    let from_display = match resolve_ens_name(
+                        &self.provider_transfer,
+                        &self.retry_config,
+                        &self.ens_cache,
+                        from_address,
+                    )
+                    .await
+                    {
+                        Ok(Some(name)) => name,
+                        Ok(None) => format!("{:?}", from_address),
+                        Err(e) => {
+                            println!("Failed to resolve ENS name for {:?}: {}", from_address, e);
+                            format!("{:?}", from_address)
+                        }
                     };
 
                     let value_in_wei: u128 = tx.value.parse().unwrap_or(0);
@@ -237,11 +524,24 @@ impl<P: Provider> Checker<P> {
                 let mut from_display = "".to_string();
                 if !exists {
                     // If it's a new transaction: resolve ENS
-                    from_display =
-                        match resolve_ens_name(&self.provider, decoded_log.inner.donor).await {
-                            Some(name) => name,
-                            None => donor.clone(),
-                        };
+                    from_display = match resolve_ens_name(
+                        &self.provider_donation,
+                        &self.retry_config,
+                        &self.ens_cache,
+                        decoded_log.inner.donor,
+                    )
+                    .await
+                    {
+                        Ok(Some(name)) => name,
+                        Ok(None) => donor.clone(),
+                        Err(e) => {
+                            println!(
+                                "Failed to resolve ENS name for {:?}: {}",
+                                decoded_log.inner.donor, e
+                            );
+                            donor.clone()
+                        }
+                    };
 
                     println!("DONATION -- From: {}", from_display);
                 }
@@ -268,59 +568,202 @@ impl<P: Provider> Checker<P> {
         }
         Ok(())
     }
+
+    /// Fetches and parses a single Etherscan API response, turning both HTTP
+    /// 429s and Etherscan's own `status == "0"` rate-limit responses into an
+    /// `Err` so `with_retry` can back off and retry instead of the caller
+    /// treating a rate-limited, empty `result` as "no transactions".
+    async fn fetch_etherscan(&self, url: &str) -> Result<EtherscanResponse> {
+        let response = self.http_client.get(url).send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            return Err(eyre::eyre!("Etherscan rate limit: HTTP 429"));
+        }
+        let parsed = response.json::<EtherscanResponse>().await?;
+        if parsed.status == "0" && is_rate_limit_error(&parsed.message) {
+            return Err(eyre::eyre!("Etherscan rate limit: {}", parsed.message));
+        }
+        Ok(parsed)
+    }
+}
+
+/// Resolve the ENS name for a given Ethereum address, verifying it via
+/// forward resolution before trusting it (see [`confirm_forward_resolution`]).
+/// Returns Ok(Some(name)), Ok(None) if there is no reverse record or it
+/// doesn't forward-confirm, or an `Err` if the registry/resolver calls
+/// could not be completed after retrying. Results are cached in `cache` for
+/// its configured TTL, and the existing 1s throttling sleep is skipped
+/// entirely on a cache hit.
+pub async fn resolve_ens_name<P: Provider + Clone>(
+    provider: &QuorumProvider<P>,
+    retry_config: &RetryConfig,
+    cache: &EnsCache,
+    address: Address,
+) -> Result<Option<String>> {
+    if let Some(cached) = cache.get(&address) {
+        return Ok(cached);
+    }
+
+    let resolved = reverse_resolve_ens_name(provider, retry_config, address).await?;
+    cache.insert(address, resolved.clone());
+    Ok(resolved)
 }
 
-/// Resolve ENS name for a given Ethereum address.
-/// Returns Some(name) or None if no reverse record.
-pub async fn resolve_ens_name<P: Provider>(provider: &P, address: Address) -> Option<String> {
-    // ENS Registry address
+/// Reverse-resolves `address` (registry `resolver(node)` -> resolver
+/// `name(node)`) and, if a name is found, forward-confirms it before
+/// returning it.
+async fn reverse_resolve_ens_name<P: Provider + Clone>(
+    provider: &QuorumProvider<P>,
+    retry_config: &RetryConfig,
+    address: Address,
+) -> Result<Option<String>> {
     tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-    let ens_registry = Address::from_str("0x00000000000C2E074eC69A0dFb2997BA6C7d2e1e").unwrap();
 
     // Step 1: Create the reverse record name
-    let reverse_name = format!("{:x}.addr.reverse", address);
+    let reverse_name = reverse_record_name(address);
 
     // Step 2: Hash the reverse name
-    let node_string = namehash(&reverse_name)?;
-    let node = FixedBytes::from_str(&node_string).unwrap();
+    let node_string = match namehash(&reverse_name) {
+        Some(n) => n,
+        None => return Ok(None),
+    };
+    let node = FixedBytes::from_str(&node_string)?;
+
+    // Step 3: resolver(bytes32 node) on the registry
+    let resolver_addr = lookup_resolver(provider, retry_config, node).await?;
+    if resolver_addr == Address::ZERO {
+        return Ok(None);
+    }
 
-    // Step 3: resolver(bytes32 node) function selector
-    let abi = JsonAbi::parse(["function resolver(bytes32) external view returns (address)"])
-        .expect("Failed to parse ABI");
+    // Step 4: name(bytes32 node) on that resolver
+    let abi = JsonAbi::parse(["function name(bytes32) external view returns (string memory)"])?;
+    let claimed_name = with_retry(retry_config, || async {
+        let abi = abi.clone();
+        provider
+            .call(move |p| {
+                let abi = abi.clone();
+                async move {
+                    let contract = ContractInstance::new(resolver_addr, p, Interface::new(abi));
+                    let return_val_raw = contract
+                        .function("name", &[DynSolValue::FixedBytes(node, 32)])
+                        .map_err(|e| eyre::eyre!(e))?
+                        .call()
+                        .await
+                        .map_err(|e| eyre::eyre!(e))?;
+                    return_val_raw[0]
+                        .as_str()
+                        .map(|s| s.to_string())
+                        .ok_or_else(|| eyre::eyre!("Expected string output from name()"))
+                }
+            })
+            .await
+    })
+    .await?;
 
-    let contract = ContractInstance::new(ens_registry, provider, Interface::new(abi));
+    if claimed_name.is_empty() {
+        return Ok(None);
+    }
 
-    let return_val_raw = contract
-        .function("resolver", &[DynSolValue::FixedBytes(node, 32)])
-        .expect("Failed to create method call")
-        .call()
-        .await
-        .expect("Failed to call resolver");
-    let resolver_addr = return_val_raw[0]
-        .as_address()
-        .expect("Expected address output");
+    // Reverse records are spoofable: anyone can point their addr.reverse
+    // record at an arbitrary name. Only trust it if the name's own forward
+    // `addr(bytes32)` record resolves back to this address.
+    if !confirm_forward_resolution(provider, retry_config, &claimed_name, address).await? {
+        println!(
+            "⚠️ ENS name {} claims to belong to {:?} but does not forward-resolve to it; ignoring",
+            claimed_name, address
+        );
+        return Ok(None);
+    }
+
+    Ok(Some(claimed_name))
+}
+
+/// Confirms `name` forward-resolves to `expected_address`: hashes `name`,
+/// looks up its resolver via the registry, and calls `addr(bytes32)` on
+/// that resolver, comparing the result to `expected_address` (case
+/// insensitively, since both are hex-encoded).
+async fn confirm_forward_resolution<P: Provider + Clone>(
+    provider: &QuorumProvider<P>,
+    retry_config: &RetryConfig,
+    name: &str,
+    expected_address: Address,
+) -> Result<bool> {
+    let node_string = match namehash(name) {
+        Some(n) => n,
+        None => return Ok(false),
+    };
+    let node = FixedBytes::from_str(&node_string)?;
+
+    let resolver_addr = lookup_resolver(provider, retry_config, node).await?;
     if resolver_addr == Address::ZERO {
-        return None;
+        return Ok(false);
     }
 
-    // Step 5: Call name(bytes32 node) on resolver
-    let abi2 = JsonAbi::parse(["function name(bytes32) external view returns (string memory)"])
-        .expect("Failed to parse ABI");
+    let abi = JsonAbi::parse(["function addr(bytes32) external view returns (address)"])?;
+    let forward_addr = with_retry(retry_config, || async {
+        let abi = abi.clone();
+        provider
+            .call(move |p| {
+                let abi = abi.clone();
+                async move {
+                    let contract = ContractInstance::new(resolver_addr, p, Interface::new(abi));
+                    let return_val_raw = contract
+                        .function("addr", &[DynSolValue::FixedBytes(node, 32)])
+                        .map_err(|e| eyre::eyre!(e))?
+                        .call()
+                        .await
+                        .map_err(|e| eyre::eyre!(e))?;
+                    return_val_raw[0]
+                        .as_address()
+                        .ok_or_else(|| eyre::eyre!("Expected address output from addr()"))
+                }
+            })
+            .await
+    })
+    .await?;
 
-    let contract2 = ContractInstance::new(resolver_addr, provider, Interface::new(abi2));
+    Ok(addresses_match(forward_addr, expected_address))
+}
 
-    let return_val_raw2 = contract2
-        .function("name", &[DynSolValue::FixedBytes(node, 32)])
-        .expect("Failed to create method call")
-        .call()
-        .await
-        .expect("Failed to call name");
-    let end_name = return_val_raw2[0].as_str().expect("Expected string output");
-    if end_name.is_empty() {
-        return None;
-    } else {
-        return Some(end_name.to_string());
-    }
+/// The ENS reverse-record name registered for `address` under `addr.reverse`.
+fn reverse_record_name(address: Address) -> String {
+    format!("{:x}.addr.reverse", address)
+}
+
+/// Compares two addresses case-insensitively, since both are hex-encoded.
+fn addresses_match(a: Address, b: Address) -> bool {
+    format!("{:x}", a) == format!("{:x}", b)
+}
+
+/// Looks up the resolver contract registered for `node` in the ENS registry.
+async fn lookup_resolver<P: Provider + Clone>(
+    provider: &QuorumProvider<P>,
+    retry_config: &RetryConfig,
+    node: FixedBytes<32>,
+) -> Result<Address> {
+    let ens_registry = Address::from_str(ENS_REGISTRY_ADDRESS).unwrap();
+    let abi = JsonAbi::parse(["function resolver(bytes32) external view returns (address)"])?;
+
+    with_retry(retry_config, || async {
+        let abi = abi.clone();
+        provider
+            .call(move |p| {
+                let abi = abi.clone();
+                async move {
+                    let contract = ContractInstance::new(ens_registry, p, Interface::new(abi));
+                    let return_val_raw = contract
+                        .function("resolver", &[DynSolValue::FixedBytes(node, 32)])
+                        .map_err(|e| eyre::eyre!(e))?
+                        .call()
+                        .await
+                        .map_err(|e| eyre::eyre!(e))?;
+                    return_val_raw[0]
+                        .as_address()
+                        .ok_or_else(|| eyre::eyre!("Expected address output from resolver()"))
+                }
+            })
+            .await
+    })
+    .await
 }
 
 pub fn namehash(name: &str) -> Option<String> {
@@ -361,3 +804,58 @@ pub fn generate_donation_hash_key(
     let result = hasher.finalize();
     hex::encode(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn namehash_of_empty_name_is_rejected() {
+        assert_eq!(namehash(""), None);
+    }
+
+    #[test]
+    fn namehash_is_deterministic() {
+        assert_eq!(namehash("alice.eth"), namehash("alice.eth"));
+    }
+
+    #[test]
+    fn namehash_distinguishes_different_names() {
+        let alice = namehash("alice.eth").unwrap();
+        let bob = namehash("bob.eth").unwrap();
+        let eth = namehash("eth").unwrap();
+        assert_ne!(alice, bob);
+        assert_ne!(alice, eth);
+        assert_ne!(bob, eth);
+    }
+
+    #[test]
+    fn namehash_distinguishes_label_boundaries() {
+        // A naive implementation that concatenated labels instead of
+        // hashing each one separately could collide "ab.c" with "abc" or
+        // "a.bc" — exactly the kind of flaw that would let a forged
+        // `addr.reverse` label alias a different name's node.
+        assert_ne!(namehash("ab.c").unwrap(), namehash("abc").unwrap());
+        assert_ne!(namehash("ab.c").unwrap(), namehash("a.bc").unwrap());
+    }
+
+    #[test]
+    fn reverse_record_name_differs_per_address() {
+        let addr_a = Address::from_str("0x0000000000000000000000000000000000000001").unwrap();
+        let addr_b = Address::from_str("0x0000000000000000000000000000000000000002").unwrap();
+        assert_ne!(reverse_record_name(addr_a), reverse_record_name(addr_b));
+        assert!(reverse_record_name(addr_a).ends_with(".addr.reverse"));
+    }
+
+    #[test]
+    fn addresses_match_rejects_a_forged_reverse_record() {
+        // Models the spoof this code defends against: an attacker's
+        // reverse record claims a name whose forward `addr()` resolves to
+        // someone else's address, not the claimant's.
+        let claimant = Address::from_str("0x0000000000000000000000000000000000000001").unwrap();
+        let actual_owner =
+            Address::from_str("0x0000000000000000000000000000000000000002").unwrap();
+        assert!(!addresses_match(actual_owner, claimant));
+        assert!(addresses_match(claimant, claimant));
+    }
+}
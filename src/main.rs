@@ -2,11 +2,14 @@
 extern crate rocket;
 
 mod checker;
+mod quorum;
+mod retry;
 mod sql;
 
-use alloy::providers::ProviderBuilder;
+use alloy::providers::{DynProvider, Provider, ProviderBuilder, WsConnect};
 use checker::Checker;
 use dotenv::dotenv;
+use quorum::{QuorumProvider, QuorumThreshold};
 use reqwest::Client;
 use rocket::http::Status;
 use rocket::response::status::Custom;
@@ -44,29 +47,83 @@ async fn health() -> &'static str {
     "OK"
 }
 
+/// Reads `key` as a comma-separated list of RPC URLs (operators can mix
+/// paid + public endpoints behind a single role).
+fn parse_rpc_urls(key: &str) -> eyre::Result<Vec<Url>> {
+    let raw = env::var(key).unwrap_or_else(|_| panic!("❌ Missing {key}"));
+    raw.split(',')
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .map(|s| Url::parse(s).map_err(|e| eyre::eyre!("Invalid URL in {key} ({s}): {e}")))
+        .collect()
+}
+
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
     println!("loading dotenv...");
     dotenv().ok();
     println!("starting...");
     let etherscan_api_key = env::var("ETHERSCAN_API_KEY").expect("❌ Missing ETHERSCAN_API_KEY");
-    let rpc_url_transfer_str = env::var("RPC_URL_TRANSFER").expect("❌ Missing RPC_URL_TRANSFER");
-    let rpc_url_transfer = Url::parse(&rpc_url_transfer_str)?;
-    let rpc_url_donation_str = env::var("RPC_URL_DONATION").expect("❌ Missing RPC_URL_DONATION");
-    let rpc_url_donation = Url::parse(&rpc_url_donation_str)?;
+    let rpc_urls_transfer = parse_rpc_urls("RPC_URL_TRANSFER")?;
+    if rpc_urls_transfer.is_empty() {
+        panic!("❌ Missing RPC_URL_TRANSFER");
+    }
+    let rpc_urls_donation = parse_rpc_urls("RPC_URL_DONATION")?;
+    if rpc_urls_donation.is_empty() {
+        panic!("❌ Missing RPC_URL_DONATION");
+    }
     let database_url = env::var("DATABASE_URL").expect("❌ Missing DATABASE_URL");
     let target_transfer_address =
         env::var("TARGET_TRANSFER_ADDRESS").expect("❌ Missing TARGET_TRANSFER_ADDRESS");
     let target_donation_address =
         env::var("TARGET_DONATION_ADDRESS").expect("❌ Missing TARGET_DONATION_ADDRESS");
     let start_block_str = env::var("START_BLOCK").expect("❌ Missing START_BLOCK");
+    let chain_id_str = env::var("CHAIN_ID").expect("❌ Missing CHAIN_ID");
 
     let start_block = start_block_str
         .parse::<u64>()
         .expect("❌ Invalid START_BLOCK");
+    let chain_id = chain_id_str.parse::<u64>().expect("❌ Invalid CHAIN_ID");
+
+    // RPC_URL_TRANSFER/RPC_URL_DONATION may each be a comma-separated list of
+    // endpoints; wrap them in a QuorumProvider so a single desynced or
+    // malicious backend can't silently corrupt tracking. A `ws://`/`wss://`
+    // donation endpoint additionally lets the checker stream donations live
+    // via `eth_subscribe` instead of polling, so all entries must agree on
+    // transport — otherwise whether that mode turns on would silently
+    // depend on list order.
+    let donation_ws_flags: Vec<bool> = rpc_urls_donation
+        .iter()
+        .map(|u| u.scheme() == "ws" || u.scheme() == "wss")
+        .collect();
+    if donation_ws_flags.iter().any(|&is_ws| is_ws != donation_ws_flags[0]) {
+        panic!("❌ RPC_URL_DONATION mixes ws(s):// and http(s):// endpoints; use one transport for all entries");
+    }
+    let donation_is_ws = donation_ws_flags[0];
 
-    let provider_transfer = ProviderBuilder::new().connect_http(rpc_url_transfer);
-    let provider_donation = ProviderBuilder::new().connect_http(rpc_url_donation);
+    let mut transfer_backends = Vec::with_capacity(rpc_urls_transfer.len());
+    for url in rpc_urls_transfer {
+        transfer_backends.push(ProviderBuilder::new().connect_http(url).erased());
+    }
+    let provider_transfer = QuorumProvider::new(
+        transfer_backends,
+        QuorumThreshold::from_env("RPC_QUORUM_TRANSFER"),
+    );
+
+    let mut donation_backends: Vec<DynProvider> = Vec::with_capacity(rpc_urls_donation.len());
+    for url in rpc_urls_donation {
+        let is_ws = url.scheme() == "ws" || url.scheme() == "wss";
+        let backend = if is_ws {
+            ProviderBuilder::new().connect_ws(WsConnect::new(url)).await?.erased()
+        } else {
+            ProviderBuilder::new().connect_http(url).erased()
+        };
+        donation_backends.push(backend);
+    }
+    let provider_donation = QuorumProvider::new(
+        donation_backends,
+        QuorumThreshold::from_env("RPC_QUORUM_DONATION"),
+    );
 
     let pg_pool = loop {
         println!("⏳ Attempting to connect to Postgres...");
@@ -94,9 +151,11 @@ async fn main() -> eyre::Result<()> {
         etherscan_api_key,
         provider_transfer,
         provider_donation,
+        donation_is_ws,
         client,
         pg_client.clone(),
         start_block,
+        chain_id,
     );
 
     // Spawn the background checker
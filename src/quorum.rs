@@ -0,0 +1,143 @@
+use eyre::Result;
+use futures_util::future::join_all;
+
+/// How many of a [`QuorumProvider`]'s backends must agree on a result.
+#[derive(Debug, Clone, Copy)]
+pub enum QuorumThreshold {
+    /// More than half of the configured backends must return the same value.
+    Majority,
+    /// At least `n` backends must return the same value (capped at the
+    /// number of configured backends).
+    AtLeast(usize),
+}
+
+impl QuorumThreshold {
+    pub fn from_env(key: &str) -> Self {
+        match std::env::var(key).ok().and_then(|v| v.parse::<usize>().ok()) {
+            Some(n) if n > 0 => QuorumThreshold::AtLeast(n),
+            _ => QuorumThreshold::Majority,
+        }
+    }
+
+    fn required(self, backend_count: usize) -> usize {
+        match self {
+            QuorumThreshold::Majority => backend_count / 2 + 1,
+            QuorumThreshold::AtLeast(n) => n.min(backend_count).max(1),
+        }
+    }
+}
+
+/// Wraps one or more RPC backends and only trusts a response once a quorum
+/// of them agree, so a single desynced or malicious endpoint returning a
+/// stale head or missing logs can't silently corrupt donation tracking.
+pub struct QuorumProvider<P> {
+    backends: Vec<P>,
+    threshold: QuorumThreshold,
+}
+
+impl<P: Clone> QuorumProvider<P> {
+    pub fn new(backends: Vec<P>, threshold: QuorumThreshold) -> Self {
+        assert!(
+            !backends.is_empty(),
+            "QuorumProvider requires at least one backend"
+        );
+        Self { backends, threshold }
+    }
+
+    /// The first configured backend, used for operations that can't be
+    /// meaningfully quorum-checked (e.g. an `eth_subscribe` stream).
+    pub fn primary(&self) -> &P {
+        &self.backends[0]
+    }
+
+    /// Dispatches `call` to every backend concurrently and returns the
+    /// value shared by at least a quorum of them, discarding stragglers.
+    /// Callers that can return results in different but equivalent orders
+    /// (e.g. `get_logs`) should normalize them inside `call` first.
+    pub async fn call<T, F, Fut>(&self, call: F) -> Result<T>
+    where
+        T: PartialEq + Clone,
+        F: Fn(P) -> Fut,
+        Fut: std::future::Future<Output = Result<T>>,
+    {
+        let responses = join_all(self.backends.iter().cloned().map(call)).await;
+
+        let mut tally: Vec<(T, usize)> = Vec::new();
+        let mut errors = Vec::new();
+        for response in responses {
+            match response {
+                Ok(value) => {
+                    if let Some(entry) = tally.iter_mut().find(|(v, _)| *v == value) {
+                        entry.1 += 1;
+                    } else {
+                        tally.push((value, 1));
+                    }
+                }
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+
+        let required = self.threshold.required(self.backends.len());
+        if let Some((value, _)) = tally.into_iter().find(|(_, count)| *count >= required) {
+            return Ok(value);
+        }
+
+        Err(eyre::eyre!(
+            "no quorum ({}/{} backends required) among responses; errors: [{}]",
+            required,
+            self.backends.len(),
+            errors.join("; ")
+        ))
+    }
+
+    /// Dispatches `call` to every backend concurrently and returns the
+    /// highest block number supported by at least a quorum of backends,
+    /// where a backend supports a candidate if it reports a block no more
+    /// than `tolerance` below it (or any block at or above it). Suited to "latest block" queries, where
+    /// backends are expected to disagree slightly due to ordinary
+    /// propagation/polling timing, but unlike plain [`QuorumProvider::call`]
+    /// this still requires quorum agreement (within `tolerance`) rather than
+    /// trusting whichever single backend reports the highest number — a
+    /// lone malicious or buggy backend must not be able to unilaterally
+    /// advance the tracked chain head to blocks that haven't been mined.
+    pub async fn call_latest_block<F, Fut>(&self, tolerance: u64, call: F) -> Result<u64>
+    where
+        F: Fn(P) -> Fut,
+        Fut: std::future::Future<Output = Result<u64>>,
+    {
+        let responses = join_all(self.backends.iter().cloned().map(call)).await;
+
+        let mut values: Vec<u64> = Vec::new();
+        let mut errors = Vec::new();
+        for response in responses {
+            match response {
+                Ok(value) => values.push(value),
+                Err(e) => errors.push(e.to_string()),
+            }
+        }
+        values.sort_unstable();
+
+        let required = self.threshold.required(self.backends.len());
+        // Walk candidates from highest to lowest, accepting the first one
+        // that a quorum of backends are at, above, or within `tolerance`
+        // below.
+        for &candidate in values.iter().rev() {
+            let agreeing = values
+                .iter()
+                .filter(|&&v| candidate.saturating_sub(v) <= tolerance)
+                .count();
+            if agreeing >= required {
+                return Ok(candidate);
+            }
+        }
+
+        Err(eyre::eyre!(
+            "no quorum ({}/{} backends required, tolerance {}) among latest-block responses {:?}; errors: [{}]",
+            required,
+            self.backends.len(),
+            tolerance,
+            values,
+            errors.join("; ")
+        ))
+    }
+}
@@ -0,0 +1,96 @@
+use eyre::Result;
+use std::env;
+use std::time::Duration;
+
+/// Exponential backoff policy for RPC and Etherscan calls, tunable via env
+/// vars so operators can loosen/tighten retry behavior per provider plan.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryConfig {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub multiplier: f64,
+}
+
+impl RetryConfig {
+    pub fn from_env() -> Self {
+        Self {
+            max_attempts: env_parse("RETRY_MAX_ATTEMPTS", 5),
+            base_delay: Duration::from_millis(env_parse("RETRY_BASE_DELAY_MS", 500)),
+            multiplier: env_parse("RETRY_BACKOFF_MULTIPLIER", 2.0),
+        }
+    }
+}
+
+fn env_parse<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+/// Returns true if `message` looks like a rate-limit response: HTTP 429,
+/// a JSON-RPC `-32005`/"rate limit" style error, or Etherscan's
+/// `status == "0"` "Max rate limit reached" message.
+pub fn is_rate_limit_error(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    lower.contains("429")
+        || lower.contains("-32005")
+        || lower.contains("rate limit")
+        || lower.contains("capacity exceeded")
+}
+
+/// Retries `f` with exponential backoff and jitter, up to
+/// `config.max_attempts` times, instead of letting callers `.expect()` and
+/// panic on the first transient failure. Rate-limit errors (per
+/// [`is_rate_limit_error`]) and other transport errors share the same
+/// backoff budget; on exhaustion the last error is returned.
+pub async fn with_retry<T, F, Fut>(config: &RetryConfig, mut f: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T>>,
+{
+    let mut delay = config.base_delay;
+    for attempt in 0..=config.max_attempts {
+        match f().await {
+            Ok(value) => return Ok(value),
+            Err(err) => {
+                if attempt == config.max_attempts {
+                    return Err(eyre::eyre!(
+                        "giving up after {} attempts: {}",
+                        config.max_attempts + 1,
+                        err
+                    ));
+                }
+                let jittered = delay + Duration::from_millis(jitter_ms());
+                if is_rate_limit_error(&err.to_string()) {
+                    eprintln!(
+                        "⏳ rate limited (attempt {}/{}), backing off {:?}: {}",
+                        attempt + 1,
+                        config.max_attempts + 1,
+                        jittered,
+                        err
+                    );
+                } else {
+                    eprintln!(
+                        "⚠️ transport error (attempt {}/{}), retrying in {:?}: {}",
+                        attempt + 1,
+                        config.max_attempts + 1,
+                        jittered,
+                        err
+                    );
+                }
+                tokio::time::sleep(jittered).await;
+                delay = delay.mul_f64(config.multiplier);
+            }
+        }
+    }
+    unreachable!("loop always returns on the final attempt")
+}
+
+fn jitter_ms() -> u64 {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    (nanos % 250) as u64
+}
@@ -126,4 +126,29 @@ impl DbClient {
             })
             .map_err(|e| eyre::eyre!("Failed to fetch donations: {}", e))
     }
+
+    /// Returns the last donation log block the backfill fully processed, so
+    /// a crash mid-backfill can resume from there instead of `start_block`.
+    pub async fn get_backfill_checkpoint(&self) -> Result<Option<u64>> {
+        sqlx::query("SELECT last_processed_block FROM backfill_checkpoint WHERE id = 1")
+            .fetch_optional(&self.pool)
+            .await
+            .map(|row| row.map(|r| r.get::<i64, _>("last_processed_block") as u64))
+            .map_err(|e| eyre::eyre!("Failed to fetch backfill checkpoint: {}", e))
+    }
+
+    pub async fn set_backfill_checkpoint(&self, last_processed_block: u64) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO backfill_checkpoint (id, last_processed_block) VALUES (1, $1)
+            ON CONFLICT (id) DO UPDATE SET last_processed_block = EXCLUDED.last_processed_block
+            "#,
+        )
+        .bind(last_processed_block as i64)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| eyre::eyre!("Failed to persist backfill checkpoint: {}", e))?;
+
+        Ok(())
+    }
 }